@@ -1,6 +1,7 @@
-use std::cell::{LazyCell, RefCell};
+use std::cell::LazyCell;
 use std::fmt::{self, Debug, Formatter};
 use std::hash::Hash;
+use std::sync::Mutex;
 
 use bumpalo::Bump;
 use bumpalo::boxed::Box as BumpBox;
@@ -673,13 +674,15 @@ impl PlacedChild<'_> {
 /// - When the computation is performed multiple times consecutively with the
 ///   same argument, reuses the cache.
 /// - When the argument changes, the new output is cached.
-#[derive(Clone)]
-struct CachedCell<T>(RefCell<Option<(u128, T)>>);
+///
+/// The cache is guarded by a mutex so that children holding a cell can be
+/// shared across threads during parallel layout.
+struct CachedCell<T>(Mutex<Option<(u128, T)>>);
 
 impl<T> CachedCell<T> {
     /// Create an empty cached cell.
     fn new() -> Self {
-        Self(RefCell::new(None))
+        Self(Mutex::new(None))
     }
 
     /// Perform the computation `f` with caching.
@@ -691,7 +694,7 @@ impl<T> CachedCell<T> {
     {
         let input_hash = typst_utils::hash128(&input);
 
-        let mut slot = self.0.borrow_mut();
+        let mut slot = self.0.lock().unwrap();
         if let Some((hash, output)) = &*slot
             && *hash == input_hash
         {
@@ -710,8 +713,51 @@ impl<T> Default for CachedCell<T> {
     }
 }
 
+impl<T: Clone> Clone for CachedCell<T> {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(self.0.lock().unwrap().clone()))
+    }
+}
+
 impl<T> Debug for CachedCell<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.pad("CachedCell(..)")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn test_cached_cell_concurrent_get_or_init() {
+        let cell = CachedCell::new();
+        let calls = AtomicUsize::new(0);
+        let outputs: Vec<usize> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    s.spawn(|| {
+                        cell.get_or_init(7_usize, |input| {
+                            calls.fetch_add(1, Ordering::SeqCst);
+                            input * 6
+                        })
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert!(outputs.iter().all(|&output| output == 42));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_cached_cell_recomputes_on_new_input() {
+        let cell = CachedCell::new();
+        assert_eq!(cell.get_or_init(1, |input| input + 1), 2);
+        assert_eq!(cell.get_or_init(1, |_| unreachable!()), 2);
+        assert_eq!(cell.get_or_init(2, |input| input + 1), 3);
+    }
+}